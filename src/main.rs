@@ -1,13 +1,39 @@
 use anyhow::{Context, Result, bail};
 use clap::Parser;
 use std::collections::VecDeque;
+use std::ffi::CString;
 use std::fs::{self, OpenOptions};
-use std::io::Write;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::os::unix::ffi::OsStrExt;
 use std::path::Path;
 use std::process::Command;
 
 use libc;
 
+/// `swapon(2)` flag bits (see `<linux/swap.h>`).
+const SWAP_FLAG_PREFER: i32 = 0x8000;
+const SWAP_FLAG_PRIO_MASK: i32 = 0x7fff;
+const SWAP_FLAG_DISCARD: i32 = 0x10000;
+
+/// Layout of the swap header that lives in the first page of a swap area
+/// (see `union swap_header` in the kernel's `<linux/swap.h>`): bytes 0..1024
+/// are the reserved boot block, then a little-endian `u32` version at 1024,
+/// `last_page` at 1028, `nr_badpages` at 1032, a 16-byte UUID at 1036 and a
+/// 16-byte NUL-padded volume label at 1052. The last 10 bytes of the page
+/// hold the `SWAPSPACE2` signature.
+const SWAP_HEADER_UUID_OFFSET: usize = 1036;
+const SWAP_HEADER_LABEL_OFFSET: usize = 1052;
+const SWAP_HEADER_LABEL_LEN: usize = 16;
+const SWAP_SIGNATURE: &[u8] = b"SWAPSPACE2";
+
+/// Default location for the swapfile `set` creates when `--path` is omitted.
+const DEFAULT_SWAPFILE_PATH: &str = "/swap-manager.swap";
+
+/// State file recording the paths of swapfiles this tool created, so `show`
+/// can mark them as managed and `remove` knows what it's allowed to delete.
+const MANAGED_STATE_DIR: &str = "/var/lib/swap-manager";
+const MANAGED_STATE_FILE: &str = "/var/lib/swap-manager/managed.list";
+
 /// swap-manager — tiny Rust tool to show / set / empty swap
 ///
 /// This program accepts a sequence of commands, for example:
@@ -15,27 +41,40 @@ use libc;
 ///   swap-manager show
 ///   swap-manager set 512M --replace --persist
 ///   swap-manager empty
+///   swap-manager label /swap-manager.swap --name mybox
+///   swap-manager set 5G --path /mnt/ssd/swap2 --persist
 #[derive(Parser, Debug)]
 #[command(
     name = "swap-manager",
     version,
-    about = "Manage swap: show, set, empty. Commands may be chained.",
+    about = "Manage swap: show, set, empty, label, remove. Commands may be chained.",
     long_about = "Maintained by Mai Bloom Tech Studio
 
 We value transparency and open-source collaboration. With that freedom comes responsibility: please test our tools in safe environments before production use. This product is provided as-is, without warranty of any kind.
 
-Manage swap: show, set, empty. Commands may be chained.
+Manage swap: show, set, empty, label, remove. Commands may be chained.
 
 USAGE EXAMPLES:
   swap-manager show
   swap-manager set 5G --replace --persist
   swap-manager set 512M show
   swap-manager set 1G --replace show empty
+  swap-manager set 2G --priority 10 --discard
+  swap-manager set 1G --no-persist
+  swap-manager set 5G --path /mnt/ssd/swap2 --persist
+  swap-manager empty --managed-only
+  swap-manager remove /mnt/ssd/swap2
+  swap-manager show --json
+  swap-manager label /swap-manager.swap
+  swap-manager label /swap-manager.swap --uuid 11111111-2222-3333-4444-555555555555 --name mybox
 
 NOTES:
 - This tool manipulates swap devices and files: run as root (sudo).
 - Test in a VM/container before using on production systems.
-- 'set' creates a swapfile at /swap-manager.swap by default.
+- 'set' creates a swapfile at /swap-manager.swap by default, or at --path.
+- 'label' reads/rewrites a swap area's UUID and volume label in place.
+- Swapfiles created by 'set' are tracked in /var/lib/swap-manager/managed.list.
+- 'show --json' prints a machine-readable JSON object (a \"swaps\" record array plus \"totals\") instead of the table.
 
 FLAGS:
   -h, --help       Print help information
@@ -76,34 +115,115 @@ fn main() -> Result<()> {
     while let Some(tok) = q.pop_front() {
         match tok.as_str() {
             "show" => {
-                show_swaps()?;
+                let mut json = false;
+                while matches!(q.front().map(String::as_str), Some(s) if s.starts_with("--")) {
+                    let flag = q.pop_front().unwrap();
+                    match flag.as_str() {
+                        "--json" => json = true,
+                        other => bail!("Unknown flag for 'show': {}", other),
+                    }
+                }
+                show_swaps(json)?;
             }
             "empty" => {
-                empty_swap()?;
+                let mut managed_only = false;
+                while matches!(q.front().map(String::as_str), Some(s) if s.starts_with("--")) {
+                    let flag = q.pop_front().unwrap();
+                    match flag.as_str() {
+                        "--managed-only" => managed_only = true,
+                        other => bail!("Unknown flag for 'empty': {}", other),
+                    }
+                }
+                empty_swap(managed_only)?;
             }
             "set" => {
                 // next token must be a size like 5G or 512M
                 let size_tok = q
                     .pop_front()
                     .ok_or_else(|| anyhow::anyhow!("'set' requires a size argument, e.g. 5G"))?;
-                // collect optional flags for this set command: --replace, --persist
+                // collect optional flags for this set command: --replace, --persist, --no-persist,
+                // --priority, --discard, --path
                 let mut replace = false;
                 let mut persist = false;
+                let mut no_persist = false;
+                let mut priority: Option<i32> = None;
+                let mut discard = false;
+                let mut path_override: Option<String> = None;
                 // peek next tokens that start with "--" and belong to set
                 while matches!(q.front().map(String::as_str), Some(s) if s.starts_with("--")) {
                     let flag = q.pop_front().unwrap();
                     match flag.as_str() {
                         "--replace" => replace = true,
                         "--persist" => persist = true,
+                        "--no-persist" => no_persist = true,
+                        "--discard" => discard = true,
+                        "--priority" => {
+                            let val = q.pop_front().ok_or_else(|| {
+                                anyhow::anyhow!("--priority requires a value, e.g. --priority 10")
+                            })?;
+                            priority = Some(val.parse().context("parsing --priority value")?);
+                        }
+                        "--path" => {
+                            let val = q.pop_front().ok_or_else(|| {
+                                anyhow::anyhow!("--path requires a value, e.g. --path /swapfile2")
+                            })?;
+                            path_override = Some(val);
+                        }
                         other => bail!("Unknown flag for 'set': {}", other),
                     }
                 }
-                set_swap(&size_tok, replace, persist)?;
+                if persist && no_persist {
+                    bail!("'--persist' and '--no-persist' are mutually exclusive");
+                }
+                set_swap(
+                    &size_tok,
+                    replace,
+                    persist,
+                    no_persist,
+                    priority,
+                    discard,
+                    path_override.as_deref(),
+                )?;
+            }
+            "remove" => {
+                let path_tok = q
+                    .pop_front()
+                    .ok_or_else(|| anyhow::anyhow!("'remove' requires a path argument"))?;
+                remove_swapfile(&path_tok)?;
+            }
+            "label" => {
+                let path_tok = q
+                    .pop_front()
+                    .ok_or_else(|| anyhow::anyhow!("'label' requires a path argument"))?;
+                let mut uuid: Option<String> = None;
+                let mut name: Option<String> = None;
+                while matches!(q.front().map(String::as_str), Some(s) if s.starts_with("--")) {
+                    let flag = q.pop_front().unwrap();
+                    match flag.as_str() {
+                        "--uuid" => {
+                            let val = q.pop_front().ok_or_else(|| {
+                                anyhow::anyhow!("--uuid requires a value, e.g. --uuid <UUID>")
+                            })?;
+                            uuid = Some(val);
+                        }
+                        "--name" => {
+                            let val = q.pop_front().ok_or_else(|| {
+                                anyhow::anyhow!("--name requires a value, e.g. --name swapfile")
+                            })?;
+                            name = Some(val);
+                        }
+                        other => bail!("Unknown flag for 'label': {}", other),
+                    }
+                }
+                label_swap(&path_tok, uuid, name)?;
             }
             other if other.starts_with('-') => {
                 bail!("Unexpected global flag or misplaced flag: {}", other)
             }
-            other => bail!("Unknown command: {} (expected set/show/empty)", other),
+            other => bail!(
+                "Unknown command: {} (expected set/show/empty/label/remove)",
+                other
+            ),
         }
     }
 
@@ -149,24 +269,394 @@ fn parse_human_size(s: &str) -> Result<u64> {
         .ok_or_else(|| anyhow::anyhow!("size overflow"))?)
 }
 
-fn show_swaps() -> Result<()> {
+fn path_to_cstring(path: &Path) -> Result<CString> {
+    CString::new(path.as_os_str().as_bytes())
+        .with_context(|| format!("path {} contains a NUL byte", path.display()))
+}
+
+/// Activate `path` as a swap area via the `swapon(2)` syscall, passing `flags`
+/// (see `SWAP_FLAG_*` above), and map a failed call into the `anyhow` chain.
+fn swapon_path(path: &Path, flags: i32) -> Result<()> {
+    let c_path = path_to_cstring(path)?;
+    let ret = unsafe { libc::swapon(c_path.as_ptr(), flags) };
+    if ret != 0 {
+        let err = std::io::Error::last_os_error();
+        bail!("swapon({}) failed: {}", path.display(), err);
+    }
+    Ok(())
+}
+
+/// Deactivate `path` via the `swapoff(2)` syscall.
+fn swapoff_path(path: &Path) -> Result<()> {
+    let c_path = path_to_cstring(path)?;
+    let ret = unsafe { libc::swapoff(c_path.as_ptr()) };
+    if ret != 0 {
+        let err = std::io::Error::last_os_error();
+        bail!("swapoff({}) failed: {}", path.display(), err);
+    }
+    Ok(())
+}
+
+fn page_size() -> usize {
+    let sz = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    if sz > 0 { sz as usize } else { 4096 }
+}
+
+fn parse_uuid(s: &str) -> Result<[u8; 16]> {
+    let hex: String = s.chars().filter(|c| *c != '-').collect();
+    if hex.len() != 32 {
+        bail!("'{}' is not a valid UUID (expected 36 chars, e.g. xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx)", s);
+    }
+    let mut bytes = [0u8; 16];
+    for (i, b) in bytes.iter_mut().enumerate() {
+        *b = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16)
+            .with_context(|| format!("parsing UUID '{}'", s))?;
+    }
+    Ok(bytes)
+}
+
+fn format_uuid(bytes: &[u8; 16]) -> String {
+    format!(
+        "{:02x}{:02x}{:02x}{:02x}-{:02x}{:02x}-{:02x}{:02x}-{:02x}{:02x}-{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}",
+        bytes[0], bytes[1], bytes[2], bytes[3],
+        bytes[4], bytes[5],
+        bytes[6], bytes[7],
+        bytes[8], bytes[9],
+        bytes[10], bytes[11], bytes[12], bytes[13], bytes[14], bytes[15],
+    )
+}
+
+/// Paths of swapfiles this tool has created, as recorded in `managed.list`.
+fn read_managed_paths() -> Result<Vec<std::path::PathBuf>> {
+    let text = match fs::read_to_string(MANAGED_STATE_FILE) {
+        Ok(t) => t,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).context("reading managed swapfile state"),
+    };
+    Ok(text
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .map(std::path::PathBuf::from)
+        .collect())
+}
+
+fn write_managed_paths(paths: &[std::path::PathBuf]) -> Result<()> {
+    fs::create_dir_all(MANAGED_STATE_DIR).context("creating /var/lib/swap-manager")?;
+    let mut content = paths
+        .iter()
+        .map(|p| p.display().to_string())
+        .collect::<Vec<_>>()
+        .join("\n");
+    if !content.is_empty() {
+        content.push('\n');
+    }
+    fs::write(MANAGED_STATE_FILE, content).context("writing managed swapfile state")
+}
+
+fn add_managed_path(path: &Path) -> Result<()> {
+    let mut paths = read_managed_paths()?;
+    if !paths.iter().any(|p| p == path) {
+        paths.push(path.to_path_buf());
+        write_managed_paths(&paths)?;
+    }
+    Ok(())
+}
+
+fn remove_managed_path(path: &Path) -> Result<()> {
+    let mut paths = read_managed_paths()?;
+    let before = paths.len();
+    paths.retain(|p| p != path);
+    if paths.len() != before {
+        write_managed_paths(&paths)?;
+    }
+    Ok(())
+}
+
+/// Resolve an `/etc/fstab` device field to an actual path `swapon(2)` can
+/// use. `UUID=...` fields are first matched against our own managed
+/// swapfiles (by reading each one's header UUID), then fall back to the
+/// kernel's `/dev/disk/by-uuid` symlinks for block devices.
+fn resolve_fstab_device(field: &str) -> std::path::PathBuf {
+    if let Some(uuid_str) = field.strip_prefix("UUID=") {
+        if let Ok(managed) = read_managed_paths() {
+            for p in &managed {
+                if let Ok(u) = read_swap_uuid(p) {
+                    if format_uuid(&u).eq_ignore_ascii_case(uuid_str) {
+                        return p.clone();
+                    }
+                }
+            }
+        }
+        return std::path::PathBuf::from(format!(
+            "/dev/disk/by-uuid/{}",
+            uuid_str.to_lowercase()
+        ));
+    }
+    std::path::PathBuf::from(field)
+}
+
+/// Read the 16-byte UUID out of a swap area's on-disk header, bailing if the
+/// `SWAPSPACE2` signature is missing.
+fn read_swap_uuid(path: &Path) -> Result<[u8; 16]> {
+    let psize = page_size();
+    let mut file =
+        fs::File::open(path).with_context(|| format!("opening {}", path.display()))?;
+    let mut page = vec![0u8; psize];
+    file.read_exact(&mut page)
+        .with_context(|| format!("reading swap header from {}", path.display()))?;
+    if &page[psize - SWAP_SIGNATURE.len()..] != SWAP_SIGNATURE {
+        bail!(
+            "{} does not look like a swap area (missing SWAPSPACE2 signature)",
+            path.display()
+        );
+    }
+    Ok(page[SWAP_HEADER_UUID_OFFSET..SWAP_HEADER_UUID_OFFSET + 16]
+        .try_into()
+        .unwrap())
+}
+
+/// Does an `/etc/fstab` device field (e.g. `UUID=...` or a raw path) refer to
+/// the same swap area as `path`/`uuid_str`?
+fn fstab_device_matches(device_field: &str, uuid_str: &str, path: &Path) -> bool {
+    if let Some(u) = device_field.strip_prefix("UUID=") {
+        return u.eq_ignore_ascii_case(uuid_str);
+    }
+    let candidate = Path::new(device_field);
+    if candidate == path {
+        return true;
+    }
+    match (fs::canonicalize(candidate), fs::canonicalize(path)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// Add/replace (or, with `remove`, strip) the managed `UUID=... none swap sw
+/// 0 0` line for `path` in `/etc/fstab`, matching existing entries by UUID or
+/// resolved path so re-running `set` never stacks duplicate entries.
+fn manage_fstab_swap_entry(path: &Path, uuid: &[u8; 16], remove: bool) -> Result<()> {
+    manage_fstab_swap_entry_at("/etc/fstab", path, uuid, remove)
+}
+
+/// Same as [`manage_fstab_swap_entry`] but against an arbitrary fstab-style
+/// file, so the de-dup logic can be exercised in tests without touching the
+/// real `/etc/fstab`. The file is rewritten atomically via a temp file + rename.
+fn manage_fstab_swap_entry_at(
+    fstab_path: &str,
+    path: &Path,
+    uuid: &[u8; 16],
+    remove: bool,
+) -> Result<()> {
+    let uuid_str = format_uuid(uuid);
+    let existing = fs::read_to_string(fstab_path).unwrap_or_default();
+
+    let managed_line = format!("UUID={} none swap sw 0 0", uuid_str);
+    let mut out_lines: Vec<&str> = Vec::new();
+    let mut matched = false;
+    for line in existing.lines() {
+        let trimmed = line.trim();
+        let fields: Vec<&str> = trimmed.split_whitespace().collect();
+        let is_match = fields.len() >= 3
+            && fields[2] == "swap"
+            && fstab_device_matches(fields[0], &uuid_str, path);
+        if is_match {
+            // The managed line takes the place of the first matching entry.
+            if !matched && !remove {
+                out_lines.push(&managed_line);
+            }
+            matched = true;
+            continue;
+        }
+        out_lines.push(line);
+    }
+
+    if !remove && !matched {
+        out_lines.push(&managed_line);
+    }
+
+    let mut content = out_lines.join("\n");
+    if !content.is_empty() {
+        content.push('\n');
+    }
+    let tmp_path = format!("{}.swap-manager.tmp", fstab_path);
+    fs::write(&tmp_path, &content).context("writing temporary fstab file")?;
+    fs::rename(&tmp_path, fstab_path).context("renaming temporary fstab file into place")?;
+
+    if remove {
+        if matched {
+            println!("Removed /etc/fstab entry for {}.", path.display());
+        } else {
+            println!(
+                "No matching /etc/fstab entry found for {} — nothing to remove.",
+                path.display()
+            );
+        }
+    } else if matched {
+        println!(
+            "Replaced existing /etc/fstab entry for {} with: {}",
+            path.display(),
+            managed_line
+        );
+    } else {
+        println!("Appended to /etc/fstab: {}", managed_line);
+    }
+
+    Ok(())
+}
+
+/// Read, and optionally rewrite, the UUID and volume label embedded in a swap
+/// area's on-disk header — without re-running `mkswap`, so an active swapfile
+/// keeps its signature.
+fn label_swap(path_str: &str, uuid: Option<String>, name: Option<String>) -> Result<()> {
+    let path = Path::new(path_str);
+    let write_mode = uuid.is_some() || name.is_some();
+    if write_mode {
+        require_root()?;
+    }
+
+    let psize = page_size();
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(write_mode)
+        .open(path)
+        .with_context(|| format!("opening {}", path.display()))?;
+    let mut page = vec![0u8; psize];
+    file.read_exact(&mut page)
+        .with_context(|| format!("reading swap header from {}", path.display()))?;
+
+    if &page[psize - SWAP_SIGNATURE.len()..] != SWAP_SIGNATURE {
+        bail!(
+            "{} does not look like a swap area (missing SWAPSPACE2 signature) — refusing to touch it",
+            path.display()
+        );
+    }
+
+    if let Some(uuid_str) = uuid {
+        let bytes = parse_uuid(&uuid_str)?;
+        page[SWAP_HEADER_UUID_OFFSET..SWAP_HEADER_UUID_OFFSET + 16].copy_from_slice(&bytes);
+    }
+    if let Some(name_str) = name {
+        let mut label = [0u8; SWAP_HEADER_LABEL_LEN];
+        let name_bytes = name_str.as_bytes();
+        let n = name_bytes.len().min(SWAP_HEADER_LABEL_LEN);
+        label[..n].copy_from_slice(&name_bytes[..n]);
+        page[SWAP_HEADER_LABEL_OFFSET..SWAP_HEADER_LABEL_OFFSET + SWAP_HEADER_LABEL_LEN]
+            .copy_from_slice(&label);
+    }
+
+    if write_mode {
+        file.seek(SeekFrom::Start(0))
+            .with_context(|| format!("seeking in {}", path.display()))?;
+        file.write_all(&page)
+            .with_context(|| format!("writing swap header to {}", path.display()))?;
+        println!("Updated swap header for {}.", path.display());
+    }
+
+    let uuid_bytes: [u8; 16] = page[SWAP_HEADER_UUID_OFFSET..SWAP_HEADER_UUID_OFFSET + 16]
+        .try_into()
+        .unwrap();
+    let label_bytes =
+        &page[SWAP_HEADER_LABEL_OFFSET..SWAP_HEADER_LABEL_OFFSET + SWAP_HEADER_LABEL_LEN];
+    let label_str = String::from_utf8_lossy(label_bytes)
+        .trim_end_matches('\0')
+        .to_string();
+    println!("UUID:  {}", format_uuid(&uuid_bytes));
+    println!("LABEL: {}", label_str);
+
+    Ok(())
+}
+
+/// One `/proc/swaps` row, normalized for both the pretty and `--json` output modes.
+struct SwapRecord<'a> {
+    line: &'a str,
+    filename: &'a str,
+    kind: &'a str,
+    size_bytes: u64,
+    used_bytes: u64,
+    priority: i64,
+    managed: bool,
+}
+
+fn json_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn print_swaps_json(records: &[SwapRecord], total_size: u64, total_used: u64) {
+    let entries: Vec<String> = records
+        .iter()
+        .map(|r| {
+            format!(
+                "{{\"filename\":\"{}\",\"type\":\"{}\",\"size_bytes\":{},\"used_bytes\":{},\"priority\":{},\"managed\":{}}}",
+                json_escape(r.filename),
+                json_escape(r.kind),
+                r.size_bytes,
+                r.used_bytes,
+                r.priority,
+                r.managed
+            )
+        })
+        .collect();
+    println!(
+        "{{\"swaps\":[{}],\"totals\":{{\"size_bytes\":{},\"used_bytes\":{}}}}}",
+        entries.join(","),
+        total_size,
+        total_used
+    );
+}
+
+fn show_swaps(json: bool) -> Result<()> {
+    let managed = read_managed_paths().unwrap_or_default();
     let text = fs::read_to_string("/proc/swaps").context("reading /proc/swaps")?;
     let mut lines = text.lines();
     let header = lines.next().unwrap_or("");
-    println!("{}", header);
+
+    let mut records = Vec::new();
     let mut total_size: u64 = 0;
     let mut total_used: u64 = 0;
     for line in lines {
         // /proc/swaps columns: Filename Type Size Used Priority
         let parts: Vec<&str> = line.split_whitespace().collect();
         if parts.len() >= 5 {
-            let size_kb: u64 = parts[2].parse().unwrap_or(0);
-            let used_kb: u64 = parts[3].parse().unwrap_or(0);
-            total_size += size_kb * 1024;
-            total_used += used_kb * 1024;
-            println!("{}", line);
+            let size_bytes = parts[2].parse::<u64>().unwrap_or(0) * 1024;
+            let used_bytes = parts[3].parse::<u64>().unwrap_or(0) * 1024;
+            total_size += size_bytes;
+            total_used += used_bytes;
+            records.push(SwapRecord {
+                line,
+                filename: parts[0],
+                kind: parts[1],
+                size_bytes,
+                used_bytes,
+                priority: parts[4].parse().unwrap_or(0),
+                managed: managed.iter().any(|p| p.as_path() == Path::new(parts[0])),
+            });
         }
     }
+
+    if json {
+        print_swaps_json(&records, total_size, total_used);
+        return Ok(());
+    }
+
+    println!("{}  Managed", header);
+    for record in &records {
+        println!(
+            "{}  {}",
+            record.line,
+            if record.managed { "yes" } else { "no" }
+        );
+    }
     println!(
         "
 Total: {} used / {} total",
@@ -176,52 +666,98 @@ Total: {} used / {} total",
     Ok(())
 }
 
-fn empty_swap() -> Result<()> {
+fn empty_swap(managed_only: bool) -> Result<()> {
     require_root()?;
+    let managed = if managed_only {
+        read_managed_paths()?
+    } else {
+        Vec::new()
+    };
+
     println!("Disabling all swap (this will move pages back into RAM)...");
-    let s = Command::new("swapoff")
-        .arg("-a")
-        .status()
-        .context("running swapoff -a")?;
-    if !s.success() {
-        bail!("swapoff failed: exit {}", s.code().unwrap_or(-1));
+    // Mirror "swapoff -a" by swapping off every device /proc/swaps reports active
+    // (with `managed_only`, only the swapfiles listed in the state file).
+    let text = fs::read_to_string("/proc/swaps").context("reading /proc/swaps")?;
+    for line in text.lines().skip(1) {
+        let filename = line
+            .split_whitespace()
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("malformed /proc/swaps line: {}", line))?;
+        let path = Path::new(filename);
+        if managed_only && !managed.iter().any(|p| p == path) {
+            continue;
+        }
+        swapoff_path(path).with_context(|| format!("disabling swap device {}", filename))?;
     }
-    println!("Re-enabling swap (swapon -a)...");
-    let s2 = Command::new("swapon")
-        .arg("-a")
-        .status()
-        .context("running swapon -a")?;
-    if !s2.success() {
-        bail!("swapon failed: exit {}", s2.code().unwrap_or(-1));
+
+    println!("Re-enabling swap from /etc/fstab...");
+    // Mirror "swapon -a" by swapping on every fstab entry whose fs type is "swap"
+    // (with `managed_only`, only entries that resolve to a managed swapfile).
+    let fstab = fs::read_to_string("/etc/fstab").unwrap_or_default();
+    for line in fstab.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = trimmed.split_whitespace().collect();
+        if fields.len() >= 3 && fields[2] == "swap" {
+            let path = resolve_fstab_device(fields[0]);
+            if managed_only && !managed.iter().any(|p| p == &path) {
+                continue;
+            }
+            swapon_path(&path, 0)
+                .with_context(|| format!("enabling swap device {}", fields[0]))?;
+        }
     }
     println!("Swap emptied (swapoff -> swapon cycle completed).");
     Ok(())
 }
 
-fn set_swap(size_token: &str, replace: bool, persist: bool) -> Result<()> {
+fn set_swap(
+    size_token: &str,
+    replace: bool,
+    persist: bool,
+    no_persist: bool,
+    priority: Option<i32>,
+    discard: bool,
+    path_override: Option<&str>,
+) -> Result<()> {
     require_root()?;
     println!(
-        "Requested set {} (replace={} persist={})",
-        size_token, replace, persist
+        "Requested set {} (replace={} persist={} no_persist={} priority={:?} discard={} path={:?})",
+        size_token, replace, persist, no_persist, priority, discard, path_override
     );
     let size_bytes = parse_human_size(size_token)?;
 
+    let mut flags: i32 = 0;
+    if let Some(p) = priority {
+        flags |= SWAP_FLAG_PREFER | (p & SWAP_FLAG_PRIO_MASK);
+    }
+    if discard {
+        flags |= SWAP_FLAG_DISCARD;
+    }
+
     if replace {
-        println!("Replacing existing swap (running swapoff -a)...");
-        let s = Command::new("swapoff")
-            .arg("-a")
-            .status()
-            .context("swapoff -a")?;
-        if !s.success() {
-            bail!("swapoff -a failed: exit {}", s.code().unwrap_or(-1));
+        println!("Replacing existing swap (disabling all active devices)...");
+        let text = fs::read_to_string("/proc/swaps").context("reading /proc/swaps")?;
+        for line in text.lines().skip(1) {
+            if let Some(filename) = line.split_whitespace().next() {
+                swapoff_path(Path::new(filename))
+                    .with_context(|| format!("disabling swap device {}", filename))?;
+            }
         }
     }
 
-    let path = Path::new("/swap-manager.swap");
+    let path = Path::new(path_override.unwrap_or(DEFAULT_SWAPFILE_PATH));
+    // Preserve the existing UUID across regeneration (if there is one) so that
+    // re-running `set --persist` on the same path updates the same fstab
+    // entry instead of leaving a dangling line pointing at a UUID `mkswap`
+    // would otherwise have randomly reassigned.
+    let existing_uuid = if path.exists() { read_swap_uuid(path).ok() } else { None };
     if path.exists() {
         // if it's active we should turn it off before overwriting
         println!("Existing {} found — disabling it first...", path.display());
-        let _ = Command::new("swapoff").arg(path).status();
+        let _ = swapoff_path(path);
         fs::remove_file(path).context("removing existing swapfile")?;
     }
 
@@ -270,21 +806,18 @@ fn set_swap(size_token: &str, replace: bool, persist: bool) -> Result<()> {
     // chmod 600
     let _ = Command::new("chmod").arg("600").arg(path).status();
     // mkswap
-    let s = Command::new("mkswap")
-        .arg(path)
-        .status()
-        .context("mkswap")?;
+    let mut mkswap_cmd = Command::new("mkswap");
+    mkswap_cmd.arg(path);
+    if let Some(uuid) = existing_uuid {
+        mkswap_cmd.arg("-U").arg(format_uuid(&uuid));
+    }
+    let s = mkswap_cmd.status().context("mkswap")?;
     if !s.success() {
         bail!("mkswap failed: exit {}", s.code().unwrap_or(-1));
     }
     // swapon
-    let s2 = Command::new("swapon")
-        .arg(path)
-        .status()
-        .context("swapon")?;
-    if !s2.success() {
-        bail!("swapon failed: exit {}", s2.code().unwrap_or(-1));
-    }
+    swapon_path(path, flags).context("swapon")?;
+    add_managed_path(path).context("recording managed swapfile state")?;
 
     println!(
         "Activated swapfile {} (size {}).",
@@ -292,29 +825,50 @@ fn set_swap(size_token: &str, replace: bool, persist: bool) -> Result<()> {
         human_readable_bytes(size_bytes)
     );
 
-    if persist {
-        println!("Adding entry to /etc/fstab to make swap persistent...");
-        let fstab_line = format!(
-            "{} none swap sw 0 0
-",
+    if persist || no_persist {
+        let uuid = read_swap_uuid(path)
+            .context("reading UUID from freshly created swap header")?;
+        manage_fstab_swap_entry(path, &uuid, no_persist)?;
+    }
+
+    Ok(())
+}
+
+/// Tear down a managed swapfile: swap it off, drop its fstab/state entries,
+/// and delete the file.
+fn remove_swapfile(path_str: &str) -> Result<()> {
+    require_root()?;
+    let path = Path::new(path_str);
+    if !read_managed_paths()?.iter().any(|p| p == path) {
+        bail!(
+            "{} is not a swapfile managed by swap-manager — refusing to remove it",
             path.display()
         );
-        let fstab = "/etc/fstab";
-        // Heuristic: don't add duplicate lines
-        let existing = fs::read_to_string(fstab).unwrap_or_default();
-        if existing.contains(&fstab_line) {
-            println!("/etc/fstab already contains the same entry — skipping append.");
-        } else {
-            let mut f = OpenOptions::new()
-                .append(true)
-                .open(fstab)
-                .context("open /etc/fstab for appending")?;
-            f.write_all(fstab_line.as_bytes())
-                .context("writing to /etc/fstab")?;
-            println!("Appended to /etc/fstab: {}", fstab_line.trim());
+    }
+
+    match read_swap_uuid(path) {
+        Ok(uuid) => {
+            manage_fstab_swap_entry(path, &uuid, true)?;
+        }
+        Err(e) => {
+            println!(
+                "Note: could not read swap header for {} ({}) — leaving /etc/fstab untouched.",
+                path.display(),
+                e
+            );
         }
     }
 
+    if let Err(e) = swapoff_path(path) {
+        println!("Note: {} (continuing to remove the file)", e);
+    }
+
+    if path.exists() {
+        fs::remove_file(path).with_context(|| format!("removing {}", path.display()))?;
+    }
+    remove_managed_path(path)?;
+
+    println!("Removed swapfile {}.", path.display());
     Ok(())
 }
 
@@ -333,3 +887,85 @@ fn human_readable_bytes(n: u64) -> String {
         format!("{:.2} {}", val, units[idx])
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    static TEST_COUNTER: AtomicU32 = AtomicU32::new(0);
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        let n = TEST_COUNTER.fetch_add(1, Ordering::SeqCst);
+        std::env::temp_dir().join(format!("swap-manager-test-{}-{}-{}", std::process::id(), n, name))
+    }
+
+    #[test]
+    fn fstab_device_matches_same_uuid() {
+        let path = Path::new("/swap-manager-test.swap");
+        assert!(fstab_device_matches(
+            "UUID=aaaaaaaa-bbbb-cccc-dddd-eeeeeeeeeeee",
+            "aaaaaaaa-bbbb-cccc-dddd-eeeeeeeeeeee",
+            path
+        ));
+    }
+
+    #[test]
+    fn fstab_device_matches_different_uuid() {
+        let path = Path::new("/swap-manager-test.swap");
+        assert!(!fstab_device_matches(
+            "UUID=11111111-1111-1111-1111-111111111111",
+            "aaaaaaaa-bbbb-cccc-dddd-eeeeeeeeeeee",
+            path
+        ));
+    }
+
+    #[test]
+    fn manage_fstab_swap_entry_dedups_repersist_same_path() {
+        // Regression test: re-running `set --persist` on the same path must
+        // update the existing fstab line in place, not stack a second one,
+        // as long as the UUID is kept stable across regeneration (see
+        // `set_swap`'s `mkswap -U`).
+        let fstab = temp_path("fstab");
+        fs::write(&fstab, "").unwrap();
+        let fstab_str = fstab.to_str().unwrap();
+        let path = Path::new("/swap-manager-test.swap");
+        let uuid = [0xaa; 16];
+
+        manage_fstab_swap_entry_at(fstab_str, path, &uuid, false).unwrap();
+        manage_fstab_swap_entry_at(fstab_str, path, &uuid, false).unwrap();
+
+        let contents = fs::read_to_string(&fstab).unwrap();
+        let swap_lines: Vec<&str> = contents.lines().filter(|l| l.contains("swap")).collect();
+        assert_eq!(
+            swap_lines.len(),
+            1,
+            "expected exactly one managed fstab line after re-persisting twice, got: {:?}",
+            swap_lines
+        );
+        assert!(swap_lines[0].contains(&format_uuid(&uuid)));
+
+        fs::remove_file(&fstab).unwrap();
+    }
+
+    #[test]
+    fn manage_fstab_swap_entry_removes_matching_line() {
+        let fstab = temp_path("fstab");
+        fs::write(&fstab, "").unwrap();
+        let fstab_str = fstab.to_str().unwrap();
+        let path = Path::new("/swap-manager-test.swap");
+        let uuid = [0xbb; 16];
+
+        manage_fstab_swap_entry_at(fstab_str, path, &uuid, false).unwrap();
+        manage_fstab_swap_entry_at(fstab_str, path, &uuid, true).unwrap();
+
+        let contents = fs::read_to_string(&fstab).unwrap();
+        assert!(
+            !contents.lines().any(|l| l.contains("swap")),
+            "expected no managed fstab line after removal, got: {:?}",
+            contents
+        );
+
+        fs::remove_file(&fstab).unwrap();
+    }
+}